@@ -0,0 +1,188 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Bumped whenever the on-disk frame layout changes, so replay can refuse
+/// capture files it doesn't understand.
+const FORMAT_VERSION: u32 = 1;
+
+/// Writes every raw 64-byte HID frame to `path`, each prefixed with a
+/// little-endian millisecond wall-clock timestamp, so a capture can be
+/// replayed later through [`Replay`] instead of the real device.
+pub struct CaptureWriter {
+    file: File,
+}
+
+impl CaptureWriter {
+    pub fn create(path: &str, append: bool, overwrite: bool) -> anyhow::Result<Self> {
+        if append && overwrite {
+            anyhow::bail!("--append and --overwrite cannot be given together");
+        }
+        let exists = Path::new(path).exists();
+        if exists && !append && !overwrite {
+            anyhow::bail!(
+                "`{path}` already exists; pass --append to continue it or --overwrite to truncate it"
+            );
+        }
+        let resume = append && exists;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(append)
+            .truncate(!append)
+            .open(path)?;
+        if !resume {
+            writeln!(
+                file,
+                "c8488-capture v{FORMAT_VERSION} start={}",
+                chrono::Local::now().to_rfc3339()
+            )?;
+        }
+        Ok(Self { file })
+    }
+
+    pub fn write_frame(&mut self, buf: &[u8; 64]) -> io::Result<()> {
+        let ts = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64;
+        self.file.write_all(&ts.to_le_bytes())?;
+        self.file.write_all(buf)?;
+        Ok(())
+    }
+}
+
+/// Reads frames back out of a file written by [`CaptureWriter`], feeding
+/// them through [`crate::message::Message::push`] in place of the device.
+pub struct Replay {
+    reader: BufReader<File>,
+}
+
+impl Replay {
+    pub fn open(path: &str) -> anyhow::Result<Self> {
+        let mut reader = BufReader::new(File::open(path)?);
+        let mut header = String::new();
+        reader.read_line(&mut header)?;
+        let version: u32 = header
+            .strip_prefix("c8488-capture v")
+            .and_then(|rest| rest.split_whitespace().next())
+            .and_then(|version| version.parse().ok())
+            .ok_or_else(|| anyhow::anyhow!("`{path}` is not a c8488 capture file"))?;
+        if version != FORMAT_VERSION {
+            anyhow::bail!(
+                "`{path}` is capture format v{version}, but this build only reads v{FORMAT_VERSION}"
+            );
+        }
+        Ok(Self { reader })
+    }
+
+    /// Returns the next recorded frame, or `None` once the capture is
+    /// exhausted.
+    pub fn next_frame(&mut self) -> anyhow::Result<Option<[u8; 64]>> {
+        let mut ts = [0; 8];
+        match self.reader.read_exact(&mut ts) {
+            Ok(()) => {}
+            Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(err) => return Err(err.into()),
+        }
+        let mut frame = [0; 64];
+        self.reader.read_exact(&mut frame)?;
+        Ok(Some(frame))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn temp_path(name: &str) -> String {
+        static COUNTER: AtomicU32 = AtomicU32::new(0);
+        let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+        std::env::temp_dir()
+            .join(format!(
+                "c8488-capture-test-{}-{}-{name}",
+                std::process::id(),
+                n
+            ))
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    #[test]
+    fn append_without_an_existing_file_creates_one() {
+        let path = temp_path("append-fresh");
+        CaptureWriter::create(&path, true, false).unwrap();
+        assert!(Path::new(&path).exists());
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn overwrite_truncates_an_existing_capture() {
+        let path = temp_path("overwrite");
+        let mut writer = CaptureWriter::create(&path, false, true).unwrap();
+        writer.write_frame(&[1; 64]).unwrap();
+        drop(writer);
+        let before = fs::metadata(&path).unwrap().len();
+        assert!(before > 64);
+
+        CaptureWriter::create(&path, false, true).unwrap();
+        let after = fs::metadata(&path).unwrap().len();
+        assert!(after < before);
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn append_and_overwrite_together_are_rejected() {
+        let path = temp_path("append-and-overwrite");
+        assert!(CaptureWriter::create(&path, true, true).is_err());
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn existing_file_without_a_flag_is_rejected() {
+        let path = temp_path("no-flag");
+        CaptureWriter::create(&path, false, true).unwrap();
+        assert!(CaptureWriter::create(&path, false, false).is_err());
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn replay_rejects_a_file_with_no_header() {
+        let path = temp_path("no-header");
+        fs::write(&path, b"not a capture file\n").unwrap();
+        assert!(Replay::open(&path).is_err());
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn replay_rejects_an_unknown_format_version() {
+        let path = temp_path("bad-version");
+        fs::write(
+            &path,
+            b"c8488-capture v99 start=2026-01-01T00:00:00+00:00\n",
+        )
+        .unwrap();
+        assert!(Replay::open(&path).is_err());
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn round_trips_frames_through_write_and_replay() {
+        let path = temp_path("round-trip");
+        let mut writer = CaptureWriter::create(&path, false, true).unwrap();
+        let first = [1; 64];
+        let second = [2; 64];
+        writer.write_frame(&first).unwrap();
+        writer.write_frame(&second).unwrap();
+        drop(writer);
+
+        let mut replay = Replay::open(&path).unwrap();
+        assert_eq!(replay.next_frame().unwrap(), Some(first));
+        assert_eq!(replay.next_frame().unwrap(), Some(second));
+        assert_eq!(replay.next_frame().unwrap(), None);
+        let _ = fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,164 @@
+use std::cell::Cell;
+
+use crate::record::WeatherRecord;
+
+/// Encodes a parsed [`WeatherRecord`] into an output sink's wire format.
+/// Implementations are selected at startup via `--format` so the
+/// assembler/parser stays oblivious to how readings end up on disk or
+/// on the wire.
+pub trait Format {
+    fn encode(&self, record: &WeatherRecord, station: &str) -> String;
+}
+
+/// InfluxDB line protocol, the original hard-coded behavior.
+#[derive(Default)]
+pub struct Influx;
+
+impl Format for Influx {
+    fn encode(&self, record: &WeatherRecord, station: &str) -> String {
+        let mut s = String::new();
+        s.push_str("weather,station=");
+        s.push_str(station);
+        s.push(' ');
+        for (key, value) in &record.fields {
+            let Some(value) = value else { continue };
+            s.push_str(key);
+            s.push('=');
+            if key.ends_with("octant") {
+                s.push('"');
+            }
+            s.push_str(value);
+            if key.ends_with("octant") {
+                s.push('"');
+            }
+            s.push(',');
+        }
+        s.pop();
+        if let Some(ts) = record.timestamp {
+            s.push(' ');
+            s.push_str(&ts.timestamp_nanos_opt().unwrap_or_default().to_string());
+        }
+        s
+    }
+}
+
+/// One JSON object per reading.
+#[derive(Default)]
+pub struct Json;
+
+impl Format for Json {
+    fn encode(&self, record: &WeatherRecord, station: &str) -> String {
+        let mut s = String::new();
+        s.push_str("{\"station\":\"");
+        s.push_str(station);
+        s.push('"');
+        for (key, value) in &record.fields {
+            let Some(value) = value else { continue };
+            s.push_str(",\"");
+            s.push_str(key);
+            s.push_str("\":");
+            if key.ends_with("octant") {
+                s.push('"');
+                s.push_str(value);
+                s.push('"');
+            } else {
+                s.push_str(value);
+            }
+        }
+        if let Some(ts) = record.timestamp {
+            s.push_str(",\"time\":\"");
+            s.push_str(&ts.to_rfc3339());
+            s.push('"');
+        }
+        s.push('}');
+        s
+    }
+}
+
+/// Header followed by comma-separated rows. `WeatherRecord::parse` emits
+/// the same channels in the same order for every reading (missing values
+/// become blank cells rather than being dropped), so the header written
+/// from the first reading stays valid for every later row.
+#[derive(Default)]
+pub struct Csv {
+    header_written: Cell<bool>,
+}
+
+impl Format for Csv {
+    fn encode(&self, record: &WeatherRecord, station: &str) -> String {
+        let mut s = String::new();
+        if !self.header_written.replace(true) {
+            s.push_str("time,station");
+            for (key, _) in &record.fields {
+                s.push(',');
+                s.push_str(key);
+            }
+            s.push('\n');
+        }
+        if let Some(ts) = record.timestamp {
+            s.push_str(&ts.to_rfc3339());
+        }
+        s.push(',');
+        s.push_str(station);
+        for (_, value) in &record.fields {
+            s.push(',');
+            if let Some(value) = value {
+                s.push_str(value);
+            }
+        }
+        s
+    }
+}
+
+/// Prometheus textfile-exposition format, one gauge line per channel.
+/// `*_octant` is a compass-point string rather than a number, so it has
+/// no sensible gauge representation and is skipped.
+#[derive(Default)]
+pub struct Prometheus;
+
+impl Format for Prometheus {
+    fn encode(&self, record: &WeatherRecord, station: &str) -> String {
+        let mut s = String::new();
+        for (key, value) in &record.fields {
+            let Some(value) = value else { continue };
+            if key.ends_with("octant") {
+                continue;
+            }
+            s.push_str("weather_");
+            s.push_str(key);
+            s.push_str("{station=\"");
+            s.push_str(station);
+            s.push_str("\"} ");
+            s.push_str(value);
+            if let Some(ts) = record.timestamp {
+                s.push(' ');
+                s.push_str(&ts.timestamp_millis().to_string());
+            }
+            s.push('\n');
+        }
+        s.pop();
+        s
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::record::WeatherRecord;
+
+    #[test]
+    fn csv_header_stays_aligned_when_later_readings_populate_different_sensors() {
+        let csv = Csv::default();
+        let first = WeatherRecord::parse(
+            "1 230101 120000 21.0 45 12.3 55 0.1 0.2 12 20 230 NE 1013 1013 3 8 21",
+        );
+        let second = WeatherRecord::parse(
+            "1 230101 120100 21.0 45 - 55 0.1 0.2 12 20 230 NE 1013 1013 3 8 21",
+        );
+        let header = csv.encode(&first, "c8488");
+        let row = csv.encode(&second, "c8488");
+        let header_columns = header.lines().next().unwrap().split(',').count();
+        let row_columns = row.split(',').count();
+        assert_eq!(header_columns, row_columns);
+    }
+}
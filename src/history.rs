@@ -0,0 +1,71 @@
+use std::collections::HashSet;
+
+use chrono::{DateTime, Duration, FixedOffset};
+
+/// Reassembles the device's on-board history ring. Every historical
+/// `0xfe` message (`history_index != 0`) carries the `(history_length,
+/// history_index)` of the page it was read from; the device re-sends
+/// overlapping pages, so we track which pairs have already been emitted
+/// and skip repeats. The live/current reading (`history_index == 0`) is
+/// never routed through this tracker — it is timestamped with the
+/// current wall-clock time instead, see `main`.
+pub struct HistoryTracker {
+    seen: HashSet<(u16, u16)>,
+    now: DateTime<FixedOffset>,
+    interval: Duration,
+}
+
+impl HistoryTracker {
+    pub fn new(now: DateTime<FixedOffset>, interval: Duration) -> Self {
+        Self {
+            seen: HashSet::new(),
+            now,
+            interval,
+        }
+    }
+
+    /// Records a `(history_length, history_index)` page and returns the
+    /// timestamp it reconstructs to, or `None` if that page was already
+    /// seen and should not be re-emitted.
+    pub fn accept(
+        &mut self,
+        history_length: u16,
+        history_index: u16,
+    ) -> Option<DateTime<FixedOffset>> {
+        if !self.seen.insert((history_length, history_index)) {
+            return None;
+        }
+        Some(self.now - self.interval * history_index as i32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tracker() -> HistoryTracker {
+        let now = DateTime::parse_from_rfc3339("2026-01-01T12:00:00+00:00").unwrap();
+        HistoryTracker::new(now, Duration::minutes(5))
+    }
+
+    #[test]
+    fn reconstructs_timestamp_from_index_and_interval() {
+        let mut tracker = tracker();
+        let timestamp = tracker.accept(10, 3).unwrap();
+        assert_eq!(timestamp, tracker.now - Duration::minutes(15));
+    }
+
+    #[test]
+    fn repeated_page_is_not_re_emitted() {
+        let mut tracker = tracker();
+        assert!(tracker.accept(10, 3).is_some());
+        assert!(tracker.accept(10, 3).is_none());
+    }
+
+    #[test]
+    fn same_index_with_different_length_is_a_distinct_page() {
+        let mut tracker = tracker();
+        assert!(tracker.accept(10, 3).is_some());
+        assert!(tracker.accept(11, 3).is_some());
+    }
+}
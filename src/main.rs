@@ -1,127 +1,103 @@
+mod capture;
+mod format;
+mod history;
+mod message;
+mod record;
+mod sink;
+mod stream;
+
 use chrono::prelude::*;
 use log::{debug, warn};
 use std::fs::File;
 use std::io::prelude::*;
-use std::str;
-use thiserror::Error;
-
-#[derive(Error, Debug)]
-enum MessageError {
-    #[error("Invalid buffer size")]
-    Buffer,
-    #[error("Invalid buffer data")]
-    Format,
-    #[error("String conversion")]
-    Utf8Error(#[from] std::str::Utf8Error),
-    #[error("Message was complete")]
-    Complete,
-}
+use std::str::FromStr;
 
-#[derive(Default)]
-struct Message {
-    data: String,
-    typ: u8,
-    length: u8,
-    current: u8,
+use capture::{CaptureWriter, Replay};
+use format::Format;
+use history::HistoryTracker;
+use message::{Message, MessageError};
+use record::WeatherRecord;
+use sink::RotatingFileSink;
+use stream::StreamServer;
+
+/// Where raw 64-byte frames come from: the real device, or a capture
+/// file written by a previous `--capture` run.
+enum Source {
+    Device(File),
+    Replay(Replay),
 }
 
-impl Message {
-    pub fn complete(&self) -> bool {
-        self.typ != 0 && self.current == self.length
+impl Source {
+    fn read_frame(&mut self, buf: &mut [u8; 64]) -> anyhow::Result<Option<usize>> {
+        match self {
+            Source::Device(dev) => {
+                let len = dev.read(buf)?;
+                Ok(if len == 0 { None } else { Some(len) })
+            }
+            Source::Replay(replay) => Ok(replay.next_frame()?.map(|frame| {
+                *buf = frame;
+                64
+            })),
+        }
     }
+}
 
-    pub fn finish(self) -> (u8, String) {
-        (self.typ, self.data)
-    }
+enum FormatArg {
+    Influx,
+    Json,
+    Csv,
+    Prometheus,
+}
 
-    pub fn push(&mut self, buf: &[u8]) -> Result<(), MessageError> {
-        if buf.len() != 64 {
-            return Err(MessageError::Buffer);
-        }
-        let msg_type = buf[0];
-        let _history_length = u16::from_be_bytes(buf[1..3].try_into().unwrap());
-        let _history_index = u16::from_be_bytes(buf[3..5].try_into().unwrap());
-        let msg_length = buf[5] >> 4;
-        let msg_index = buf[5] & 0xf;
-        let payload_length = buf[6] as usize;
-        let payload = &buf[7..61][..payload_length];
-        let _crc = u16::from_be_bytes(buf[61..63].try_into().unwrap());
-        let end = buf[63];
-        if self.typ == 0 {
-            self.typ = msg_type;
-            self.length = msg_length;
-        }
-        if self.current >= self.length {
-            Err(MessageError::Complete)
-        } else if (self.typ, self.length, self.current + 1, 0xfd)
-            != (msg_type, msg_length, msg_index, end)
-        {
-            Err(MessageError::Format)
-        } else {
-            let payload = str::from_utf8(payload)?;
-            debug!("payload: {payload}");
-            self.data.push_str(payload);
-            self.current += 1;
-            Ok(())
+impl FromStr for FormatArg {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "influx" => Ok(Self::Influx),
+            "json" => Ok(Self::Json),
+            "csv" => Ok(Self::Csv),
+            "prometheus" => Ok(Self::Prometheus),
+            other => Err(format!("unknown format `{other}`")),
         }
     }
 }
 
-fn idb(msg: &str, station: &str) -> String {
-    let mut s = String::new();
-    s.push_str("weather,station=");
-    s.push_str(station);
-    s.push(' ');
-    for (value, key) in msg.split(' ').zip([
-        "channel",
-        "_date",
-        "_time",
-        "indoor_temp",
-        "indoor_humidity",
-        "temp",     // outdoor
-        "humidity", // outdoor
-        "rain",     // rain mm/d
-        "rate",     // rain mm/h
-        "wind",     // wind mean km/h
-        "gust",     // wind gusts km/h
-        "dir",      // wind direction
-        "wind_octant",
-        "pressure",
-        "pressure_local",
-        "uv_index",
-        "dew", // outdoor
-        "outdoor_heat_index",
-        "sensor1_temp",
-        "sensor1_humidity",
-        "sensor2_temp",
-        "sensor2_humidity",
-        "sensor3_temp",
-        "sensor3_humidity",
-        "sensor4_temp",
-        "sensor4_humidity",
-        "sensor5_temp",
-        "sensor5_humidity",
-        "sensor6_temp",
-        "sensor6_humidity",
-        "sensor7_temp",
-        "sensor7_humidity",
-    ]) {
-        if key.starts_with('_') || value.chars().all(|s| "-.".contains(s)) {
-            continue;
-        }
-        s.push_str(key);
-        s.push('=');
-        if key.ends_with("octant") {
-            s.push('"');
-        }
-        s.push_str(value);
-        if key.ends_with("octant") {
-            s.push('"');
+impl FormatArg {
+    fn build(self) -> Box<dyn Format> {
+        match self {
+            Self::Influx => Box::new(format::Influx),
+            Self::Json => Box::new(format::Json),
+            Self::Csv => Box::new(format::Csv::default()),
+            Self::Prometheus => Box::new(format::Prometheus),
         }
-        s.push(',');
     }
-    s.pop();
-    s
+}
+
+/// The current time in the configured `--timezone`, or the local
+/// timezone if none was given. Called once at startup to set the
+/// device's clock, and again for every live reading when `--history`
+/// is timestamping output.
+fn current_time(tz_offset: Option<i32>) -> DateTime<FixedOffset> {
+    match tz_offset {
+        Some(tz) => Utc::now().with_timezone(&FixedOffset::east_opt(tz * 3600).unwrap()),
+        None => Local::now().into(),
+    }
+}
+
+fn emit(
+    s: &str,
+    server: Option<&StreamServer>,
+    file: Option<&mut RotatingFileSink>,
+) -> anyhow::Result<()> {
+    println!("{s}");
+    if let Some(server) = server {
+        server.publish(s);
+    }
+    if let Some(file) = file {
+        file.write_line(s)?;
+    }
+    Ok(())
 }
 
 fn main() -> anyhow::Result<()> {
@@ -130,47 +106,86 @@ fn main() -> anyhow::Result<()> {
         .init();
 
     let mut args = pico_args::Arguments::from_env();
-    let mut dev = File::options().read(true).write(true).open(
-        args.opt_value_from_str("--device")?
-            .unwrap_or_else(|| "/dev/hidraw0".to_string()),
-    )?;
+    let replay: Option<String> = args.opt_value_from_str("--replay")?;
+    let capture: Option<String> = args.opt_value_from_str("--capture")?;
+    let append = args.contains("--append");
+    let overwrite = args.contains("--overwrite");
+    let mut capture = capture
+        .map(|path| CaptureWriter::create(&path, append, overwrite))
+        .transpose()?;
 
-    let dt = if let Some(tz) = args.opt_value_from_str::<_, i32>("--timezone")? {
-        Utc::now().with_timezone(&FixedOffset::east_opt(tz * 3600).unwrap())
+    let tz_offset: Option<i32> = args.opt_value_from_str("--timezone")?;
+    let now = current_time(tz_offset);
+
+    let mut source = if let Some(path) = replay {
+        Source::Replay(Replay::open(&path)?)
     } else {
-        Local::now().into()
+        let mut dev = File::options().read(true).write(true).open(
+            args.opt_value_from_str("--device")?
+                .unwrap_or_else(|| "/dev/hidraw0".to_string()),
+        )?;
+
+        let mut buf = [0; 9];
+        buf[1] = 0xfc;
+        buf[8] = 0xfd;
+        buf[2] = 0x08;
+        buf[3] = (now.year() - 2000) as _;
+        buf[4] = now.month() as _;
+        buf[5] = now.day() as _;
+        dev.write_all(&buf)?;
+        buf[2] = 0x09;
+        buf[3] = now.hour() as _;
+        buf[4] = now.minute() as _;
+        buf[5] = now.second() as _;
+        dev.write_all(&buf)?;
+
+        Source::Device(dev)
     };
-    let mut buf = [0; 9];
-    buf[1] = 0xfc;
-    buf[8] = 0xfd;
-    buf[2] = 0x08;
-    buf[3] = (dt.year() - 2000) as _;
-    buf[4] = dt.month() as _;
-    buf[5] = dt.day() as _;
-    dev.write_all(&buf)?;
-    buf[2] = 0x09;
-    buf[3] = dt.hour() as _;
-    buf[4] = dt.minute() as _;
-    buf[5] = dt.second() as _;
-    dev.write_all(&buf)?;
 
     let station = args
         .opt_value_from_str("--station")?
         .unwrap_or_else(|| "c8488".to_string());
-    let socket = std::net::UdpSocket::bind(
-        args.opt_value_from_str("--bind")?
-            .unwrap_or_else(|| "0.0.0.0:0".to_string()),
-    )?;
-    let target: Option<std::net::SocketAddr> = args.opt_value_from_str("--target")?;
+    let listen: Option<String> = args.opt_value_from_str("--listen")?;
+    let backlog = args.opt_value_from_str("--backlog")?.unwrap_or(1024);
+    let server = listen
+        .map(|addr| StreamServer::bind(&addr, backlog))
+        .transpose()?;
+    if let Some(server) = &server {
+        debug!("streaming on {}", server.local_addr());
+    }
     let every = args.opt_value_from_str("--every")?.unwrap_or(0);
+    let format = args
+        .opt_value_from_str::<_, FormatArg>("--format")?
+        .unwrap_or(FormatArg::Influx)
+        .build();
+    let history_interval = args
+        .opt_value_from_str("--history-interval")?
+        .unwrap_or(300);
+    let mut history = args
+        .contains("--history")
+        .then(|| HistoryTracker::new(now, chrono::Duration::seconds(history_interval)));
+
+    let file: Option<String> = args.opt_value_from_str("--file")?;
+    let file_capacity = args.opt_value_from_str("--file-capacity")?.unwrap_or(0);
+    let file_generations = args.opt_value_from_str("--file-generations")?.unwrap_or(5);
+    let time_format: Option<String> = args.opt_value_from_str("--time-format")?;
+    let mut file = file
+        .map(|path| RotatingFileSink::create(path, file_capacity, file_generations, time_format))
+        .transpose()?;
 
     let mut buf = [0; 64];
     let mut msg = Message::default();
 
     let mut i = 0;
     loop {
-        let len = dev.read(&mut buf)?;
+        let len = match source.read_frame(&mut buf)? {
+            Some(len) => len,
+            None => break,
+        };
         debug!("frame: {:X?}", &buf[..len]);
+        if let Some(cap) = capture.as_mut() {
+            cap.write_frame(&buf)?;
+        }
         if match msg.push(&buf[..len]) {
             Err(MessageError::Complete) => true,
             Err(MessageError::Buffer) => Err(MessageError::Buffer)?,
@@ -183,20 +198,39 @@ fn main() -> anyhow::Result<()> {
             msg = Message::default();
         }
         if msg.complete() {
+            let (history_length, history_index) = msg.history();
             let (typ, body) = msg.finish();
             msg = Message::default();
             match typ {
                 // human-readable message, SI units
                 0xfe => {
-                    if i > 0 {
+                    // history_index == 0 is the station's current/live
+                    // reading; anything else is a backfilled page from
+                    // the on-board history ring, and only those go
+                    // through the history tracker (when --history was
+                    // given at all). This keeps --every downsampling in
+                    // effect regardless of --history.
+                    if let Some(timestamp) = history
+                        .as_mut()
+                        .filter(|_| history_index != 0)
+                        .and_then(|history| history.accept(history_length, history_index))
+                    {
+                        let mut record = WeatherRecord::parse(&body);
+                        record.timestamp = Some(timestamp);
+                        let s = format.encode(&record, &station);
+                        emit(&s, server.as_ref(), file.as_mut())?;
+                    } else if history_index != 0 && history.is_some() {
+                        // a backfill page the tracker has already seen; idempotent no-op
+                    } else if i > 0 {
                         i -= 1;
                     } else {
                         i = every;
-                        let s = idb(&body, &station);
-                        println!("{}", s);
-                        if let Some(t) = target.as_ref() {
-                            socket.send_to(s.as_bytes(), t)?;
+                        let mut record = WeatherRecord::parse(&body);
+                        if history.is_some() {
+                            record.timestamp = Some(current_time(tz_offset));
                         }
+                        let s = format.encode(&record, &station);
+                        emit(&s, server.as_ref(), file.as_mut())?;
                     }
                 }
                 // urlencode imperial units
@@ -207,4 +241,5 @@ fn main() -> anyhow::Result<()> {
             };
         }
     }
+    Ok(())
 }
@@ -0,0 +1,146 @@
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum MessageError {
+    #[error("Invalid buffer size")]
+    Buffer,
+    #[error("Invalid buffer data")]
+    Format,
+    #[error("String conversion")]
+    Utf8Error(#[from] std::str::Utf8Error),
+    #[error("Message was complete")]
+    Complete,
+}
+
+#[derive(Default)]
+pub struct Message {
+    data: String,
+    typ: u8,
+    length: u8,
+    current: u8,
+    history_length: u16,
+    history_index: u16,
+}
+
+impl Message {
+    pub fn complete(&self) -> bool {
+        self.typ != 0 && self.current == self.length
+    }
+
+    /// The device's on-board history ring position this message was
+    /// tagged with: `(history_length, history_index)`. Constant across
+    /// all frames of one message, since it is read from the first frame.
+    pub fn history(&self) -> (u16, u16) {
+        (self.history_length, self.history_index)
+    }
+
+    pub fn finish(self) -> (u8, String) {
+        (self.typ, self.data)
+    }
+
+    pub fn push(&mut self, buf: &[u8]) -> Result<(), MessageError> {
+        if buf.len() != 64 {
+            return Err(MessageError::Buffer);
+        }
+        let msg_type = buf[0];
+        let history_length = u16::from_be_bytes(buf[1..3].try_into().unwrap());
+        let history_index = u16::from_be_bytes(buf[3..5].try_into().unwrap());
+        let msg_length = buf[5] >> 4;
+        let msg_index = buf[5] & 0xf;
+        let payload_length = buf[6] as usize;
+        let payload = &buf[7..61][..payload_length];
+        let _crc = u16::from_be_bytes(buf[61..63].try_into().unwrap());
+        let end = buf[63];
+        if self.typ == 0 {
+            self.typ = msg_type;
+            self.length = msg_length;
+            self.history_length = history_length;
+            self.history_index = history_index;
+        }
+        if self.current >= self.length {
+            Err(MessageError::Complete)
+        } else if (self.typ, self.length, self.current + 1, 0xfd)
+            != (msg_type, msg_length, msg_index, end)
+        {
+            Err(MessageError::Format)
+        } else {
+            let payload = std::str::from_utf8(payload)?;
+            log::debug!("payload: {payload}");
+            self.data.push_str(payload);
+            self.current += 1;
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(
+        typ: u8,
+        history_length: u16,
+        history_index: u16,
+        msg_length: u8,
+        msg_index: u8,
+        payload: &str,
+    ) -> [u8; 64] {
+        let mut buf = [0; 64];
+        buf[0] = typ;
+        buf[1..3].copy_from_slice(&history_length.to_be_bytes());
+        buf[3..5].copy_from_slice(&history_index.to_be_bytes());
+        buf[5] = (msg_length << 4) | msg_index;
+        buf[6] = payload.len() as u8;
+        buf[7..7 + payload.len()].copy_from_slice(payload.as_bytes());
+        buf[63] = 0xfd;
+        buf
+    }
+
+    #[test]
+    fn rejects_wrong_buffer_length() {
+        let mut msg = Message::default();
+        assert!(matches!(msg.push(&[0; 32]), Err(MessageError::Buffer)));
+    }
+
+    #[test]
+    fn assembles_a_single_frame_message() {
+        let mut msg = Message::default();
+        msg.push(&frame(0xfe, 7, 0, 1, 1, "hello")).unwrap();
+        assert!(msg.complete());
+        assert_eq!(msg.history(), (7, 0));
+        let (typ, body) = msg.finish();
+        assert_eq!(typ, 0xfe);
+        assert_eq!(body, "hello");
+    }
+
+    #[test]
+    fn assembles_a_multi_frame_message_in_order() {
+        let mut msg = Message::default();
+        msg.push(&frame(0xfe, 0, 0, 2, 1, "ab")).unwrap();
+        assert!(!msg.complete());
+        msg.push(&frame(0xfe, 0, 0, 2, 2, "cd")).unwrap();
+        assert!(msg.complete());
+        let (_, body) = msg.finish();
+        assert_eq!(body, "abcd");
+    }
+
+    #[test]
+    fn rejects_an_out_of_sequence_frame() {
+        let mut msg = Message::default();
+        msg.push(&frame(0xfe, 0, 0, 2, 1, "ab")).unwrap();
+        assert!(matches!(
+            msg.push(&frame(0xfe, 0, 0, 2, 1, "ab")),
+            Err(MessageError::Format)
+        ));
+    }
+
+    #[test]
+    fn rejects_a_frame_pushed_after_completion() {
+        let mut msg = Message::default();
+        msg.push(&frame(0xfe, 0, 0, 1, 1, "hi")).unwrap();
+        assert!(matches!(
+            msg.push(&frame(0xfe, 0, 0, 1, 1, "hi")),
+            Err(MessageError::Complete)
+        ));
+    }
+}
@@ -0,0 +1,109 @@
+/// Channel names for the space-separated fields of an `0xfe` human-readable
+/// message, in the order the station transmits them.
+const FIELDS: &[&str] = &[
+    "channel",
+    "_date",
+    "_time",
+    "indoor_temp",
+    "indoor_humidity",
+    "temp",     // outdoor
+    "humidity", // outdoor
+    "rain",     // rain mm/d
+    "rate",     // rain mm/h
+    "wind",     // wind mean km/h
+    "gust",     // wind gusts km/h
+    "dir",      // wind direction
+    "wind_octant",
+    "pressure",
+    "pressure_local",
+    "uv_index",
+    "dew", // outdoor
+    "outdoor_heat_index",
+    "sensor1_temp",
+    "sensor1_humidity",
+    "sensor2_temp",
+    "sensor2_humidity",
+    "sensor3_temp",
+    "sensor3_humidity",
+    "sensor4_temp",
+    "sensor4_humidity",
+    "sensor5_temp",
+    "sensor5_humidity",
+    "sensor6_temp",
+    "sensor6_humidity",
+    "sensor7_temp",
+    "sensor7_humidity",
+];
+
+/// A parsed weather reading: the named, non-underscore channels of one
+/// `0xfe` message, always in the same order with one entry per channel
+/// regardless of which values this particular reading populated — a `-`
+/// or `.` placeholder parses to `None` rather than being dropped, so the
+/// field set a reading produces never depends on its own contents. This
+/// keeps formatters that rely on a fixed column set (e.g. CSV) safe to
+/// use across readings whose populated sensors vary from tick to tick.
+#[derive(Default)]
+pub struct WeatherRecord {
+    pub fields: Vec<(&'static str, Option<String>)>,
+    /// Set for backfilled history-buffer samples, whose time is
+    /// reconstructed, and, when history backfill is enabled, for live
+    /// readings too (with the time they were read). Otherwise `None`.
+    pub timestamp: Option<chrono::DateTime<chrono::FixedOffset>>,
+}
+
+impl WeatherRecord {
+    pub fn parse(msg: &str) -> Self {
+        let fields = msg
+            .split(' ')
+            .zip(FIELDS.iter())
+            .filter(|(_, key)| !key.starts_with('_'))
+            .map(|(value, key)| {
+                let value = (!value.chars().all(|c| "-.".contains(c))).then(|| value.to_string());
+                (*key, value)
+            })
+            .collect();
+        WeatherRecord {
+            fields,
+            timestamp: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drops_underscore_fields_but_keeps_a_fixed_shape() {
+        let msg = "1 230101 120000 21.0 45 - 55 . 0.2 12 20 230 NE 1013 1013 3 8 21";
+        let record = WeatherRecord::parse(msg);
+        assert!(record.fields.iter().all(|(key, _)| !key.starts_with('_')));
+        let keys: Vec<_> = record.fields.iter().map(|(key, _)| *key).collect();
+        assert_eq!(keys[0], "channel");
+        assert_eq!(keys[1], "indoor_temp");
+        assert_eq!(
+            record.fields.iter().find(|(k, _)| *k == "temp").unwrap().1,
+            None
+        );
+        assert_eq!(
+            record
+                .fields
+                .iter()
+                .find(|(k, _)| *k == "humidity")
+                .unwrap()
+                .1,
+            Some("55".to_string())
+        );
+    }
+
+    #[test]
+    fn field_shape_is_stable_regardless_of_which_values_are_present() {
+        let full = "1 230101 120000 21.0 45 12.3 55 0.1 0.2 12 20 230 NE 1013 1013 3 8 21";
+        let sparse = "1 230101 120000 21.0 45 - 55 . 0.2 12 20 230 NE 1013 1013 3 8 21";
+        let full = WeatherRecord::parse(full);
+        let sparse = WeatherRecord::parse(sparse);
+        let full_keys: Vec<_> = full.fields.iter().map(|(key, _)| *key).collect();
+        let sparse_keys: Vec<_> = sparse.fields.iter().map(|(key, _)| *key).collect();
+        assert_eq!(full_keys, sparse_keys);
+    }
+}
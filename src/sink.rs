@@ -0,0 +1,145 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+use chrono::Local;
+
+/// Appends formatted readings to a local file, rotating to a fresh file
+/// before a line would take it past `capacity` bytes and keeping a bounded number
+/// of prior generations (`path.1` the newest, up to `path.<max_generations>`),
+/// so a headless station keeps a durable record without an external
+/// database or collector.
+pub struct RotatingFileSink {
+    path: String,
+    capacity: u64,
+    max_generations: u32,
+    time_format: Option<String>,
+    file: File,
+    size: u64,
+}
+
+impl RotatingFileSink {
+    pub fn create(
+        path: String,
+        capacity: u64,
+        max_generations: u32,
+        time_format: Option<String>,
+    ) -> anyhow::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let size = file.metadata()?.len();
+        Ok(Self {
+            path,
+            capacity,
+            max_generations,
+            time_format,
+            file,
+            size,
+        })
+    }
+
+    pub fn write_line(&mut self, line: &str) -> anyhow::Result<()> {
+        let mut out = String::new();
+        if let Some(format) = &self.time_format {
+            out.push_str(&Local::now().format(format).to_string());
+            out.push(' ');
+        }
+        out.push_str(line);
+        out.push('\n');
+
+        if self.capacity > 0 && self.size + out.len() as u64 > self.capacity {
+            self.rotate()?;
+        }
+        self.file.write_all(out.as_bytes())?;
+        self.size += out.len() as u64;
+        Ok(())
+    }
+
+    fn rotate(&mut self) -> anyhow::Result<()> {
+        for generation in (1..self.max_generations).rev() {
+            let from = format!("{}.{generation}", self.path);
+            if Path::new(&from).exists() {
+                fs::rename(&from, format!("{}.{}", self.path, generation + 1))?;
+            }
+        }
+        fs::rename(&self.path, format!("{}.1", self.path))?;
+        self.file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(&self.path)?;
+        self.size = 0;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn temp_path(name: &str) -> String {
+        static COUNTER: AtomicU32 = AtomicU32::new(0);
+        let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+        std::env::temp_dir()
+            .join(format!(
+                "c8488-sink-test-{}-{}-{name}",
+                std::process::id(),
+                n
+            ))
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    fn cleanup(path: &str, generations: u32) {
+        let _ = fs::remove_file(path);
+        for generation in 1..=generations {
+            let _ = fs::remove_file(format!("{path}.{generation}"));
+        }
+    }
+
+    #[test]
+    fn appends_without_rotating_when_under_capacity() {
+        let path = temp_path("plain");
+        let mut sink = RotatingFileSink::create(path.clone(), 0, 5, None).unwrap();
+        sink.write_line("one").unwrap();
+        sink.write_line("two").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "one\ntwo\n");
+        cleanup(&path, 5);
+    }
+
+    #[test]
+    fn rotates_once_capacity_is_exceeded() {
+        let path = temp_path("rotate");
+        let mut sink = RotatingFileSink::create(path.clone(), 8, 5, None).unwrap();
+        sink.write_line("first").unwrap();
+        sink.write_line("second").unwrap();
+        assert_eq!(fs::read_to_string(format!("{path}.1")).unwrap(), "first\n");
+        assert_eq!(fs::read_to_string(&path).unwrap(), "second\n");
+        cleanup(&path, 5);
+    }
+
+    #[test]
+    fn keeps_only_a_bounded_number_of_generations() {
+        let path = temp_path("bounded");
+        let mut sink = RotatingFileSink::create(path.clone(), 1, 2, None).unwrap();
+        for line in ["a", "b", "c", "d"] {
+            sink.write_line(line).unwrap();
+        }
+        assert!(Path::new(&format!("{path}.1")).exists());
+        assert!(Path::new(&format!("{path}.2")).exists());
+        assert!(!Path::new(&format!("{path}.3")).exists());
+        cleanup(&path, 2);
+    }
+
+    #[test]
+    fn prefixes_each_line_with_the_configured_time_format() {
+        let path = temp_path("time-format");
+        let mut sink =
+            RotatingFileSink::create(path.clone(), 0, 5, Some("%Y".to_string())).unwrap();
+        sink.write_line("reading").unwrap();
+        let contents = fs::read_to_string(&path).unwrap();
+        assert!(contents.starts_with(&Local::now().format("%Y").to_string()));
+        assert!(contents.trim_end().ends_with("reading"));
+        cleanup(&path, 5);
+    }
+}
@@ -0,0 +1,201 @@
+use log::warn;
+use std::collections::VecDeque;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::{Arc, Mutex, mpsc};
+use std::thread;
+use std::time::Duration;
+
+/// How long to wait for a client to send a start sequence before giving
+/// up and streaming from now. A client that never writes anything (just
+/// connects and reads) must not hang forever waiting for input it was
+/// never going to send.
+const START_SEQUENCE_TIMEOUT: Duration = Duration::from_millis(200);
+
+struct Shared {
+    buffer: VecDeque<(u64, String)>,
+    capacity: usize,
+    next_seq: u64,
+    subscribers: Vec<mpsc::Sender<(u64, String)>>,
+}
+
+/// A TCP server that fans formatted readings out to any number of
+/// subscribers. Every reading is tagged with a monotonically increasing
+/// sequence number and kept in a ring buffer of the last `capacity`
+/// readings, so a client that sends a start sequence on connect is first
+/// replayed everything it missed, then streamed new readings as they
+/// arrive — unlike the UDP target it replaces, a client that briefly
+/// drops its connection can resume without gaps.
+pub struct StreamServer {
+    shared: Arc<Mutex<Shared>>,
+    local_addr: SocketAddr,
+}
+
+impl StreamServer {
+    pub fn bind(addr: &str, capacity: usize) -> anyhow::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let local_addr = listener.local_addr()?;
+        let shared = Arc::new(Mutex::new(Shared {
+            buffer: VecDeque::with_capacity(capacity),
+            capacity,
+            next_seq: 0,
+            subscribers: Vec::new(),
+        }));
+        let accept_shared = shared.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                match stream {
+                    Ok(stream) => {
+                        let shared = accept_shared.clone();
+                        thread::spawn(move || {
+                            if let Err(err) = Self::serve(stream, shared) {
+                                warn!("stream client error: {err}");
+                            }
+                        });
+                    }
+                    Err(err) => warn!("stream accept error: {err}"),
+                }
+            }
+        });
+        Ok(Self { shared, local_addr })
+    }
+
+    /// The address actually bound, useful when `addr` asked for an
+    /// OS-assigned port (e.g. `"127.0.0.1:0"`).
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Replays the backlog from the client's requested start sequence (if
+    /// any), then blocks forwarding newly published readings until the
+    /// client disconnects.
+    fn serve(mut stream: TcpStream, shared: Arc<Mutex<Shared>>) -> io::Result<()> {
+        let header_stream = stream.try_clone()?;
+        header_stream.set_read_timeout(Some(START_SEQUENCE_TIMEOUT))?;
+        let mut line = String::new();
+        let start: u64 = match BufReader::new(header_stream).read_line(&mut line) {
+            Ok(0) => u64::MAX, // client closed its write half without sending one
+            Ok(_) => line.trim().parse().unwrap_or(u64::MAX),
+            Err(err)
+                if matches!(
+                    err.kind(),
+                    io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                ) =>
+            {
+                u64::MAX // no start sequence arrived in time; stream from now
+            }
+            Err(err) => return Err(err),
+        };
+
+        let (backfill, rx) = {
+            let mut shared = shared.lock().unwrap();
+            let backfill: Vec<_> = shared
+                .buffer
+                .iter()
+                .filter(|(seq, _)| *seq >= start)
+                .cloned()
+                .collect();
+            let (tx, rx) = mpsc::channel();
+            shared.subscribers.push(tx);
+            (backfill, rx)
+        };
+
+        for (_, record) in backfill {
+            writeln!(stream, "{record}")?;
+        }
+        for (_, record) in rx {
+            writeln!(stream, "{record}")?;
+        }
+        Ok(())
+    }
+
+    /// Tags `record` with the next sequence number, buffers it, and
+    /// fans it out to every connected subscriber.
+    pub fn publish(&self, record: &str) {
+        let mut shared = self.shared.lock().unwrap();
+        let seq = shared.next_seq;
+        shared.next_seq += 1;
+        if shared.capacity > 0 {
+            if shared.buffer.len() == shared.capacity {
+                shared.buffer.pop_front();
+            }
+            shared.buffer.push_back((seq, record.to_string()));
+        }
+        shared
+            .subscribers
+            .retain(|tx| tx.send((seq, record.to_string())).is_ok());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    fn connect(server: &StreamServer) -> TcpStream {
+        TcpStream::connect(server.local_addr()).unwrap()
+    }
+
+    fn read_lines(stream: &mut TcpStream, count: usize) -> Vec<String> {
+        stream
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let mut reader = BufReader::new(stream);
+        let mut lines = Vec::new();
+        while lines.len() < count {
+            let mut line = String::new();
+            let n = reader.read_line(&mut line).unwrap();
+            assert!(n > 0, "connection closed before {count} lines arrived");
+            lines.push(line.trim_end().to_string());
+        }
+        lines
+    }
+
+    #[test]
+    fn backfills_from_the_requested_sequence_then_streams_live() {
+        let server = StreamServer::bind("127.0.0.1:0", 16).unwrap();
+        server.publish("one");
+        server.publish("two");
+
+        let mut client = connect(&server);
+        writeln!(client, "1").unwrap();
+        // give the accept thread a moment to register the subscriber
+        // before we publish the live reading it should also see
+        thread::sleep(Duration::from_millis(50));
+        server.publish("three");
+
+        let lines = read_lines(&mut client, 2);
+        assert_eq!(lines, vec!["two", "three"]);
+    }
+
+    #[test]
+    fn a_client_with_no_start_sequence_only_gets_new_records() {
+        let server = StreamServer::bind("127.0.0.1:0", 16).unwrap();
+        server.publish("before");
+
+        let mut client = connect(&server);
+        client.shutdown(std::net::Shutdown::Write).unwrap();
+
+        let start = Instant::now();
+        thread::sleep(START_SEQUENCE_TIMEOUT + Duration::from_millis(50));
+        server.publish("after");
+        let lines = read_lines(&mut client, 1);
+        assert_eq!(lines, vec!["after"]);
+        assert!(start.elapsed() < Duration::from_secs(2));
+    }
+
+    #[test]
+    fn two_subscribers_each_see_every_published_record() {
+        let server = StreamServer::bind("127.0.0.1:0", 16).unwrap();
+        let mut a = connect(&server);
+        let mut b = connect(&server);
+        writeln!(a, "0").unwrap();
+        writeln!(b, "0").unwrap();
+        thread::sleep(Duration::from_millis(50));
+
+        server.publish("hello");
+
+        assert_eq!(read_lines(&mut a, 1), vec!["hello"]);
+        assert_eq!(read_lines(&mut b, 1), vec!["hello"]);
+    }
+}